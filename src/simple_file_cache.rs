@@ -1,11 +1,53 @@
 use std::fmt::Display;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use enc::hex::HexEncoder;
 use enc::StringEncoder;
-use file_storage::{Error, FilePath, FolderPath};
+use file_storage::{Error, FilePath, FolderPath, Path};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 use sha2::digest::{DynDigest, Update};
 use sha2::Sha256;
 
+/// The length, in bytes, of a lower-case hex-encoded SHA-256 digest.
+const SHA256_HEX_LEN: usize = 64;
+
+/// The length, in bytes, of a zero-padded decimal unix-timestamp header.
+const TIMESTAMP_LEN: usize = 20;
+
+/// The one-byte format marker for an uncompressed, stored entry.
+const FORMAT_STORED: u8 = 0;
+
+/// The one-byte format marker for a DEFLATE-compressed entry.
+const FORMAT_DEFLATE: u8 = 1;
+
+/// The one-byte format marker for an entry written by `put_with_ttl`, chosen well outside the
+/// byte ranges `put`'s format markers and `put_verified`'s format marker can produce, so TTL
+/// entries are never mistaken for (or by) another `put*` flavor sharing the same cache folder.
+const TTL_FORMAT_MARKER: u8 = 0xFE;
+
+/// The one-byte format marker for an entry written by `put_verified`, chosen well outside the
+/// byte ranges `put`'s format markers and `put_with_ttl`'s marker can produce, so a `put_verified`
+/// entry is never mistaken for (or by) another `put*` flavor sharing the same cache folder.
+const FORMAT_VERIFIED: u8 = 0xFD;
+
+/// The name of the file, within `cache_folder`, that records the cache version.
+const CACHE_META_FILE: &str = "cache.meta";
+
+/// The result of decoding a `put`/`with_compression` entry's stored bytes.
+enum BodyDecode {
+    /// The entry decoded successfully.
+    Ok(Vec<u8>),
+    /// The entry carries a `put` format marker but failed to decode (a truncated or tampered
+    /// DEFLATE stream).
+    Corrupt,
+    /// The entry carries a marker `put` never writes, i.e. it belongs to another `put*` flavor.
+    Foreign,
+}
+
 /// A simple file cache.
 ///
 /// # Keys
@@ -14,6 +56,8 @@ use sha2::Sha256;
 #[derive(Clone, Debug)]
 pub struct SimpleFileCache {
     cache_folder: FolderPath,
+    compression: Option<Compression>,
+    max_bytes: Option<u64>,
 }
 
 impl SimpleFileCache {
@@ -23,11 +67,104 @@ impl SimpleFileCache {
     pub fn temp() -> Result<Self, std::io::Error> {
         Ok(Self::from(FolderPath::temp()?))
     }
+
+    /// Creates a cache rooted in the standard per-user cache directory for `app_name`.
+    ///
+    /// Resolves `XDG_CACHE_HOME`, falling back to `$HOME/.cache`, joins `app_name`, and creates
+    /// the folder if it does not already exist. Returns an error if no base cache directory can
+    /// be determined.
+    ///
+    /// This only resolves Unix-style paths (`Path::unix_root`-based); on Windows it succeeds only
+    /// if `XDG_CACHE_HOME` is set to a Unix-style path, since there is no `%LOCALAPPDATA%`
+    /// resolution here yet.
+    pub fn user_cache(app_name: &str) -> Result<Self, std::io::Error> {
+        let base: String = Self::user_cache_base()?;
+
+        let mut relative: String = base.trim_start_matches('/').to_string();
+        if !relative.is_empty() && !relative.ends_with('/') {
+            relative.push('/');
+        }
+        relative.push_str(app_name);
+
+        let folder: FolderPath = Path::unix_root().with_appended(relative).make_folder();
+        std::fs::create_dir_all(folder.as_str())?;
+
+        Ok(Self::from(folder))
+    }
+
+    /// Resolves the base directory `user_cache` joins `app_name` onto.
+    fn user_cache_base() -> Result<String, std::io::Error> {
+        if let Ok(xdg_cache_home) = std::env::var("XDG_CACHE_HOME") {
+            if !xdg_cache_home.is_empty() {
+                return Ok(xdg_cache_home);
+            }
+        }
+
+        if let Ok(home) = std::env::var("HOME") {
+            if !home.is_empty() {
+                return Ok(format!("{home}/.cache"));
+            }
+        }
+
+        Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "could not determine a user cache directory",
+        ))
+    }
+
+    /// Enables DEFLATE compression of entries written by `put`.
+    ///
+    /// The on-disk format is tagged with a one-byte marker, so a cache can transparently mix
+    /// entries written before and after this is enabled.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Creates a cache rooted at `cache_folder`, namespaced to `version`.
+    ///
+    /// The version is recorded in a `cache.meta` file the first time the cache is used. If
+    /// `cache.meta` is missing or records a different version, the folder's contents are wiped
+    /// and `cache.meta` is rewritten, so a schema/format bump never hands back stale bytes.
+    pub fn with_version(cache_folder: FolderPath, version: u32) -> Result<Self, Error> {
+        let meta_file: FilePath = cache_folder
+            .path()
+            .clone_append(CACHE_META_FILE)
+            .to_file()
+            .unwrap();
+
+        let current_version: Option<u32> = meta_file
+            .read_as_vec_if_exists()?
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .and_then(|text| text.trim().parse().ok());
+
+        if current_version != Some(version) {
+            for path in Self::cache_file_paths(&cache_folder)? {
+                let _ = std::fs::remove_file(path);
+            }
+            meta_file.delete_if_exists()?;
+            meta_file.write_data(version.to_string())?;
+        }
+
+        Ok(Self::from(cache_folder))
+    }
+
+    /// Bounds the cache to `max_bytes` total, evicting the least-recently-touched entries.
+    ///
+    /// Eviction runs after every `put`; see `evict_to_fit` to also trigger it manually.
+    pub fn with_capacity(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
 }
 
 impl From<FolderPath> for SimpleFileCache {
     fn from(cache_folder: FolderPath) -> Self {
-        Self { cache_folder }
+        Self {
+            cache_folder,
+            compression: None,
+            max_bytes: None,
+        }
     }
 }
 
@@ -36,50 +173,478 @@ impl SimpleFileCache {
 
     /// Gets the file path for the key.
     pub fn file_path<K>(&self, key: K) -> FilePath
+    where
+        K: Display,
+    {
+        self.cache_folder
+            .path()
+            .clone_append(Self::relative_path(key))
+            .to_file()
+            .unwrap()
+    }
+
+    /// Computes the hash-derived path for `key`, relative to a cache folder.
+    fn relative_path<K>(key: K) -> String
     where
         K: Display,
     {
         let key: String = key.to_string();
+        let hash: String = Self::hash_hex(key.as_bytes());
+        format!("{}/{}.cache", &hash[..4], &hash[4..])
+    }
 
+    /// Computes the lower-case hex-encoded SHA-256 digest of `data`.
+    fn hash_hex(data: &[u8]) -> String {
         let mut hasher: Sha256 = Sha256::default();
-        Update::update(&mut hasher, key.as_bytes());
+        Update::update(&mut hasher, data);
         let hash: Box<[u8]> = Box::new(hasher).finalize();
-        let hash: String = HexEncoder::LOWER.encode_as_string(hash.as_ref()).unwrap();
-        let extension: String = format!("{}/{}.cache", &hash[..4], &hash[4..]);
-        let file_path: FilePath = self
+        HexEncoder::LOWER.encode_as_string(hash.as_ref()).unwrap()
+    }
+}
+
+impl SimpleFileCache {
+    //! Put
+
+    /// Puts the data into the cache.
+    ///
+    /// If compression is enabled (see `with_compression`), the data is DEFLATE-encoded before
+    /// being written. The write is atomic: the data lands in a uniquely-named temp file first,
+    /// then is renamed over the final path, so a concurrent `get` always observes either the
+    /// complete old entry or the complete new one, never a missing or half-written file.
+    pub fn put<K, D>(&self, key: K, data: D) -> Result<(), std::io::Error>
+    where
+        K: Display,
+        D: AsRef<[u8]>,
+    {
+        let data: &[u8] = data.as_ref();
+
+        let mut body: Vec<u8> = Vec::with_capacity(data.len() + 1);
+        match self.compression {
+            Some(compression) => {
+                body.push(FORMAT_DEFLATE);
+                let mut encoder: ZlibEncoder<Vec<u8>> = ZlibEncoder::new(Vec::new(), compression);
+                encoder
+                    .write_all(data)
+                    .expect("in-memory compression should not fail");
+                body.extend(
+                    encoder
+                        .finish()
+                        .expect("in-memory compression should not fail"),
+                );
+            }
+            None => {
+                body.push(FORMAT_STORED);
+                body.extend_from_slice(data);
+            }
+        }
+
+        let relative: String = Self::relative_path(key);
+        let temp_relative: String = format!(
+            "{}.{}-{}.tmp",
+            relative,
+            std::process::id(),
+            Self::now_nanos(),
+        );
+
+        let temp_file: FilePath = self
+            .cache_folder
+            .path()
+            .clone_append(temp_relative)
+            .to_file()
+            .unwrap();
+        let file: FilePath = self
             .cache_folder
             .path()
-            .clone_append(extension)
+            .clone_append(relative)
             .to_file()
             .unwrap();
-        file_path
+
+        temp_file.write_data(body)?;
+        std::fs::rename(temp_file.as_str(), file.as_str())?;
+
+        self.evict_to_fit_except(Some(PathBuf::from(file.as_str())))?;
+        Ok(())
     }
 }
 
 impl SimpleFileCache {
-    //! Put
+    //! Get
 
-    /// Puts the data into the cache.
-    pub fn put<K, D>(&self, key: K, data: D) -> Result<(), Error>
+    /// Gets the data in the cache, transparently decompressing it if needed.
+    ///
+    /// If a capacity is configured (see `with_capacity`), a hit refreshes the entry's recency so
+    /// hot keys survive eviction. An entry written by `put_verified`/`put_with_ttl` (which use a
+    /// different on-disk format) is reported as a miss rather than handed back undecoded; a
+    /// `put`/`with_compression` entry that fails to decompress is treated like any other
+    /// corruption: deleted, and reported as a miss.
+    pub fn get<K>(&self, key: K) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: Display,
+    {
+        let file: FilePath = self.file_path(key);
+        match file.read_as_vec_if_exists()? {
+            None => Ok(None),
+            Some(contents) if contents.is_empty() => Ok(Some(contents)),
+            Some(contents) => match Self::decode_body(&contents) {
+                BodyDecode::Ok(data) => {
+                    if self.max_bytes.is_some() {
+                        Self::touch(&file);
+                    }
+                    Ok(Some(data))
+                }
+                BodyDecode::Corrupt => {
+                    file.delete_if_exists()?;
+                    Ok(None)
+                }
+                BodyDecode::Foreign => Ok(None),
+            },
+        }
+    }
+
+    /// Strips the one-byte format marker `put` prepends and decompresses the body if needed.
+    ///
+    /// Returns `BodyDecode::Foreign` if `contents` carries a marker `put` never writes (i.e. the
+    /// entry belongs to `put_verified`/`put_with_ttl`), and `BodyDecode::Corrupt` if it carries a
+    /// `put` marker but fails to decode (a truncated or tampered DEFLATE stream).
+    fn decode_body(contents: &[u8]) -> BodyDecode {
+        let (format, body) = contents.split_at(1);
+        match format[0] {
+            FORMAT_STORED => BodyDecode::Ok(body.to_vec()),
+            FORMAT_DEFLATE => {
+                let mut decoder: ZlibDecoder<&[u8]> = ZlibDecoder::new(body);
+                let mut data: Vec<u8> = Vec::new();
+                match decoder.read_to_end(&mut data) {
+                    Ok(_) => BodyDecode::Ok(data),
+                    Err(_) => BodyDecode::Corrupt,
+                }
+            }
+            _ => BodyDecode::Foreign,
+        }
+    }
+}
+
+impl SimpleFileCache {
+    //! Eviction
+
+    /// Evicts the least-recently-touched entries until the cache is back under its configured
+    /// capacity (see `with_capacity`). Does nothing if no capacity is configured.
+    pub fn evict_to_fit(&self) -> Result<(), Error> {
+        self.evict_to_fit_except(None)
+    }
+
+    /// Evicts the least-recently-touched entries until the cache is back under its configured
+    /// capacity, never evicting `protect` (the file a just-completed `put` wrote), so a `put` is
+    /// never undone by the eviction pass it triggers.
+    fn evict_to_fit_except(&self, protect: Option<PathBuf>) -> Result<(), Error> {
+        let max_bytes: u64 = match self.max_bytes {
+            Some(max_bytes) => max_bytes,
+            None => return Ok(()),
+        };
+
+        let mut entries: Vec<(PathBuf, u64, SystemTime)> = Vec::new();
+        let mut total_bytes: u64 = 0;
+        for path in Self::cache_file_paths(&self.cache_folder)? {
+            if let Ok(metadata) = std::fs::metadata(&path) {
+                let modified: SystemTime = metadata.modified().unwrap_or(UNIX_EPOCH);
+                total_bytes += metadata.len();
+                entries.push((path, metadata.len(), modified));
+            }
+        }
+
+        if total_bytes <= max_bytes {
+            return Ok(());
+        }
+
+        // Sort oldest-first; break ties on path so eviction order is deterministic even when
+        // multiple entries share a modification time at the filesystem's resolution.
+        entries.sort_by(|(a_path, _, a_modified), (b_path, _, b_modified)| {
+            a_modified.cmp(b_modified).then_with(|| a_path.cmp(b_path))
+        });
+
+        for (path, size, _) in entries {
+            if total_bytes <= max_bytes {
+                break;
+            }
+            if protect.as_deref() == Some(path.as_path()) {
+                continue;
+            }
+            if std::fs::remove_file(&path).is_ok() {
+                total_bytes = total_bytes.saturating_sub(size);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Refreshes a file's modification time to now, marking it as recently used.
+    fn touch(file: &FilePath) {
+        if let Ok(opened) = std::fs::File::open(file.as_str()) {
+            let _ = opened.set_modified(SystemTime::now());
+        }
+    }
+}
+
+/// A read-through cache that writes to a primary `SimpleFileCache` and services misses from an
+/// ordered list of read-only fallback folders.
+///
+/// This supports patterns like a fast local scratch cache backed by a shared, immutable base
+/// cache (e.g. a CI artifact directory mounted read-only). `put` always writes to the primary;
+/// `get` probes the primary, then each fallback in order, returning the first hit.
+#[derive(Clone, Debug)]
+pub struct CacheStack {
+    primary: SimpleFileCache,
+    fallbacks: Vec<FolderPath>,
+}
+
+impl CacheStack {
+    //! Construction
+
+    /// Creates a cache stack with the given writable `primary` cache and read-only `fallbacks`,
+    /// probed in order after the primary on a miss.
+    pub fn new(primary: SimpleFileCache, fallbacks: Vec<FolderPath>) -> Self {
+        Self { primary, fallbacks }
+    }
+}
+
+impl CacheStack {
+    //! Put/Get
+
+    /// Puts the data into the primary cache.
+    pub fn put<K, D>(&self, key: K, data: D) -> Result<(), std::io::Error>
+    where
+        K: Display,
+        D: AsRef<[u8]>,
+    {
+        self.primary.put(key, data)
+    }
+
+    /// Gets the data from the primary cache, falling back to each read-only folder in order.
+    pub fn get<K>(&self, key: K) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: Display,
+    {
+        if let Some(data) = self.primary.get(&key)? {
+            return Ok(Some(data));
+        }
+
+        let relative: String = SimpleFileCache::relative_path(key);
+        for folder in &self.fallbacks {
+            let file: FilePath = folder
+                .path()
+                .clone_append(relative.clone())
+                .to_file()
+                .unwrap();
+            match file.read_as_vec_if_exists()? {
+                None => continue,
+                Some(contents) if contents.is_empty() => return Ok(Some(contents)),
+                Some(contents) => match SimpleFileCache::decode_body(&contents) {
+                    BodyDecode::Ok(data) => return Ok(Some(data)),
+                    // Fallback folders are read-only: a corrupt or foreign entry there is just
+                    // skipped, never deleted.
+                    BodyDecode::Corrupt | BodyDecode::Foreign => continue,
+                },
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl SimpleFileCache {
+    //! Verified Put/Get
+
+    /// Puts the data into the cache, prefixing it with a format marker and the SHA-256 digest of
+    /// `data`.
+    ///
+    /// `get_verified` uses the marker to recognize its own entries and the digest to detect
+    /// corrupted or partially-written files.
+    pub fn put_verified<K, D>(&self, key: K, data: D) -> Result<(), Error>
     where
         K: Display,
         D: AsRef<[u8]>,
     {
+        let data: &[u8] = data.as_ref();
+        let digest: String = Self::hash_hex(data);
+
+        let mut body: Vec<u8> = Vec::with_capacity(1 + digest.len() + data.len());
+        body.push(FORMAT_VERIFIED);
+        body.extend_from_slice(digest.as_bytes());
+        body.extend_from_slice(data);
+
         let file: FilePath = self.file_path(key);
         file.delete_if_exists()?;
-        file.write_data(data)
+        file.write_data(body)
+    }
+
+    /// Gets the data in the cache, verifying it against the stored content-integrity header.
+    ///
+    /// An entry whose marker byte is not `FORMAT_VERIFIED` belongs to another `put*` flavor (e.g.
+    /// `put`/`put_with_ttl`), so it is reported as a clean miss and left untouched. If the marker
+    /// matches but the recomputed digest does not match the header, the file is corrupt
+    /// (truncation, partial write, external tampering), so it is deleted and reported as a miss
+    /// rather than handed back to the caller.
+    pub fn get_verified<K>(&self, key: K) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: Display,
+    {
+        let file: FilePath = self.file_path(key);
+        match file.read_as_vec_if_exists()? {
+            None => Ok(None),
+            Some(contents) => {
+                if contents.first() != Some(&FORMAT_VERIFIED) {
+                    return Ok(None);
+                }
+                let contents: &[u8] = &contents[1..];
+
+                if contents.len() < SHA256_HEX_LEN {
+                    file.delete_if_exists()?;
+                    return Ok(None);
+                }
+
+                let (header, body) = contents.split_at(SHA256_HEX_LEN);
+                let expected: &str = std::str::from_utf8(header).unwrap_or_default();
+                let actual: String = Self::hash_hex(body);
+
+                if actual != expected {
+                    file.delete_if_exists()?;
+                    Ok(None)
+                } else {
+                    Ok(Some(body.to_vec()))
+                }
+            }
+        }
     }
 }
 
 impl SimpleFileCache {
-    //! Get
+    //! Time-To-Live
 
-    /// Gets the data in the cache.
-    pub fn get<K>(&self, key: K) -> Result<Option<Vec<u8>>, Error>
+    /// Puts the data into the cache, recording an expiration time `ttl` from now.
+    ///
+    /// The entry is tagged with `TTL_FORMAT_MARKER` ahead of the expiration header, so
+    /// `get_fresh`/`prune_expired` can tell a TTL entry apart from one written by `put`,
+    /// `put_verified`, etc. and never misinterpret (or prune) their bytes.
+    ///
+    /// Use `get_fresh` to read the entry back; it treats an expired entry as a miss.
+    pub fn put_with_ttl<K, D>(&self, key: K, data: D, ttl: Duration) -> Result<(), Error>
+    where
+        K: Display,
+        D: AsRef<[u8]>,
+    {
+        let expires_at: u64 = Self::now_secs().saturating_add(ttl.as_secs());
+        let header: String = format!("{:0width$}", expires_at, width = TIMESTAMP_LEN);
+
+        let data: &[u8] = data.as_ref();
+        let mut body: Vec<u8> = Vec::with_capacity(1 + header.len() + data.len());
+        body.push(TTL_FORMAT_MARKER);
+        body.extend_from_slice(header.as_bytes());
+        body.extend_from_slice(data);
+
+        let file: FilePath = self.file_path(key);
+        file.delete_if_exists()?;
+        file.write_data(body)
+    }
+
+    /// Gets the data in the cache, written by `put_with_ttl`, if it has not yet expired.
+    ///
+    /// If the entry's recorded expiration time has passed, it is deleted and treated as a miss.
+    /// An entry that was not written by `put_with_ttl` (no `TTL_FORMAT_MARKER` header) is also
+    /// reported as a miss, but is left untouched on disk.
+    pub fn get_fresh<K>(&self, key: K) -> Result<Option<Vec<u8>>, Error>
     where
         K: Display,
     {
-        self.file_path(key).read_as_vec_if_exists()
+        let file: FilePath = self.file_path(key);
+        match file.read_as_vec_if_exists()? {
+            None => Ok(None),
+            Some(contents) => match Self::parse_ttl_entry(&contents) {
+                None => Ok(None),
+                Some((expires_at, body)) => {
+                    if Self::now_secs() > expires_at {
+                        file.delete_if_exists()?;
+                        Ok(None)
+                    } else {
+                        Ok(Some(body.to_vec()))
+                    }
+                }
+            },
+        }
+    }
+
+    /// Removes all entries, written by `put_with_ttl`, whose expiration time has passed.
+    ///
+    /// Entries written by other `put*` methods are recognized by their missing
+    /// `TTL_FORMAT_MARKER` header and left untouched.
+    pub fn prune_expired(&self) -> Result<(), Error> {
+        let now: u64 = Self::now_secs();
+        for path in Self::cache_file_paths(&self.cache_folder)? {
+            let contents: Vec<u8> = match std::fs::read(&path) {
+                Ok(contents) => contents,
+                Err(_) => continue,
+            };
+            if let Some((expires_at, _)) = Self::parse_ttl_entry(&contents) {
+                if now > expires_at {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses a `put_with_ttl`-formatted entry into its expiration time and body, returning
+    /// `None` if `contents` was not written by `put_with_ttl`.
+    fn parse_ttl_entry(contents: &[u8]) -> Option<(u64, &[u8])> {
+        if contents.len() < 1 + TIMESTAMP_LEN || contents[0] != TTL_FORMAT_MARKER {
+            return None;
+        }
+
+        let (header, body) = contents[1..].split_at(TIMESTAMP_LEN);
+        let expires_at: u64 = std::str::from_utf8(header).ok()?.parse().ok()?;
+        Some((expires_at, body))
+    }
+
+    /// Gets the current unix time, in seconds.
+    fn now_secs() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    /// Gets the current unix time, in nanoseconds, used to make temp file names unique.
+    fn now_nanos() -> u128 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    }
+
+    /// Walks the hash-sharded subfolders of `folder`, collecting the paths of all `.cache` files.
+    fn cache_file_paths(folder: &FolderPath) -> Result<Vec<PathBuf>, Error> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+
+        let root: PathBuf = PathBuf::from(folder.as_str());
+        let Ok(shards) = std::fs::read_dir(&root) else {
+            return Ok(paths);
+        };
+
+        for shard in shards.flatten() {
+            let shard_path: PathBuf = shard.path();
+            if !shard_path.is_dir() {
+                continue;
+            }
+            let Ok(files) = std::fs::read_dir(&shard_path) else {
+                continue;
+            };
+            for file in files.flatten() {
+                let file_path: PathBuf = file.path();
+                if file_path.extension().map(|ext| ext == "cache").unwrap_or(false) {
+                    paths.push(file_path);
+                }
+            }
+        }
+
+        Ok(paths)
     }
 }
 
@@ -87,7 +652,7 @@ impl SimpleFileCache {
 mod tests {
     use file_storage::{FilePath, FolderPath, Path};
 
-    use crate::SimpleFileCache;
+    use crate::{CacheStack, SimpleFileCache, FORMAT_VERIFIED};
 
     #[test]
     fn file_path() {
@@ -117,4 +682,199 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn put_get_with_compression() -> Result<(), Box<dyn std::error::Error>> {
+        let cache: SimpleFileCache =
+            SimpleFileCache::temp()?.with_compression(flate2::Compression::default());
+        assert_eq!(cache.get("key")?, None);
+
+        cache.put("key", "data")?;
+        let result: Option<Vec<u8>> = cache.get("key")?;
+        assert!(result.is_some());
+
+        let result: String = String::from_utf8(result.unwrap())?;
+        assert_eq!(result, "data");
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_version_wipes_on_version_change() -> Result<(), Box<dyn std::error::Error>> {
+        let folder: FolderPath = FolderPath::temp()?;
+
+        let cache: SimpleFileCache = SimpleFileCache::with_version(folder.clone(), 1)?;
+        cache.put("key", "data")?;
+        assert!(cache.get("key")?.is_some());
+
+        let cache: SimpleFileCache = SimpleFileCache::with_version(folder.clone(), 2)?;
+        assert_eq!(cache.get("key")?, None);
+
+        let cache: SimpleFileCache = SimpleFileCache::with_version(folder, 2)?;
+        cache.put("key", "data")?;
+        assert!(cache.get("key")?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn cache_stack_falls_back_to_read_only_folders() -> Result<(), Box<dyn std::error::Error>> {
+        let base_folder: FolderPath = FolderPath::temp()?;
+        let base_cache: SimpleFileCache = SimpleFileCache::from(base_folder.clone());
+        base_cache.put("shared", "base-data")?;
+
+        let primary: SimpleFileCache = SimpleFileCache::temp()?;
+        let stack: CacheStack = CacheStack::new(primary.clone(), vec![base_folder]);
+
+        assert_eq!(stack.get("missing")?, None);
+
+        let result: String = String::from_utf8(stack.get("shared")?.unwrap())?;
+        assert_eq!(result, "base-data");
+
+        stack.put("shared", "primary-data")?;
+        let result: String = String::from_utf8(stack.get("shared")?.unwrap())?;
+        assert_eq!(result, "primary-data");
+
+        Ok(())
+    }
+
+    #[test]
+    fn user_cache_creates_and_reuses_the_app_folder() -> Result<(), Box<dyn std::error::Error>> {
+        std::env::set_var("XDG_CACHE_HOME", std::env::temp_dir().join("simple-file-cache-test"));
+
+        let cache: SimpleFileCache = SimpleFileCache::user_cache("my-app")?;
+        cache.put("key", "data")?;
+
+        let cache: SimpleFileCache = SimpleFileCache::user_cache("my-app")?;
+        let result: String = String::from_utf8(cache.get("key")?.unwrap())?;
+        assert_eq!(result, "data");
+
+        std::env::remove_var("XDG_CACHE_HOME");
+
+        Ok(())
+    }
+
+    #[test]
+    fn evict_to_fit_removes_least_recently_touched_entries() -> Result<(), Box<dyn std::error::Error>>
+    {
+        // Each entry is 9 bytes on disk: the 1-byte format marker `put` prepends, plus the
+        // 8-byte payload. A budget of one entry's worth keeps only the most recently written.
+        let cache: SimpleFileCache = SimpleFileCache::temp()?.with_capacity(9);
+
+        cache.put("a", "aaaaaaaa")?;
+        cache.put("b", "bbbbbbbb")?;
+
+        assert!(cache.get("a")?.is_none());
+        assert!(cache.get("b")?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn put_verified_get_verified() -> Result<(), Box<dyn std::error::Error>> {
+        let cache: SimpleFileCache = SimpleFileCache::temp()?;
+        assert_eq!(cache.get_verified("key")?, None);
+
+        cache.put_verified("key", "data")?;
+        let result: Option<Vec<u8>> = cache.get_verified("key")?;
+        assert!(result.is_some());
+
+        let result: String = String::from_utf8(result.unwrap())?;
+        assert_eq!(result, "data");
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_verified_detects_corruption() -> Result<(), Box<dyn std::error::Error>> {
+        let cache: SimpleFileCache = SimpleFileCache::temp()?;
+        cache.put_verified("key", "data")?;
+
+        let file_path: FilePath = cache.file_path("key");
+        file_path.delete_if_exists()?;
+        let mut corrupted: Vec<u8> = vec![FORMAT_VERIFIED];
+        corrupted.extend_from_slice(b"corrupted-body-with-a-stale-digest");
+        file_path.write_data(corrupted)?;
+
+        let result: Option<Vec<u8>> = cache.get_verified("key")?;
+        assert_eq!(result, None);
+        assert!(!file_path.exists()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn put_with_ttl_get_fresh() -> Result<(), Box<dyn std::error::Error>> {
+        use std::time::Duration;
+
+        let cache: SimpleFileCache = SimpleFileCache::temp()?;
+        assert_eq!(cache.get_fresh("key")?, None);
+
+        cache.put_with_ttl("key", "data", Duration::from_secs(3600))?;
+        let result: Option<Vec<u8>> = cache.get_fresh("key")?;
+        assert!(result.is_some());
+
+        let result: String = String::from_utf8(result.unwrap())?;
+        assert_eq!(result, "data");
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_fresh_expires_stale_entries() -> Result<(), Box<dyn std::error::Error>> {
+        use std::time::Duration;
+
+        let cache: SimpleFileCache = SimpleFileCache::temp()?;
+        cache.put_with_ttl("key", "data", Duration::from_secs(0))?;
+
+        // Expiration is checked in whole seconds (`now > expires_at`), so wait until the
+        // write's second has passed before the ttl-0 entry is stale.
+        std::thread::sleep(Duration::from_secs(1));
+
+        let result: Option<Vec<u8>> = cache.get_fresh("key")?;
+        assert_eq!(result, None);
+        assert!(!cache.file_path("key").exists()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn prune_expired_removes_stale_entries() -> Result<(), Box<dyn std::error::Error>> {
+        use std::time::Duration;
+
+        let cache: SimpleFileCache = SimpleFileCache::temp()?;
+        cache.put_with_ttl("stale", "data", Duration::from_secs(0))?;
+        cache.put_with_ttl("fresh", "data", Duration::from_secs(3600))?;
+
+        // Expiration is checked in whole seconds (`now > expires_at`), so wait until the
+        // write's second has passed before the ttl-0 entry is stale.
+        std::thread::sleep(Duration::from_secs(1));
+
+        cache.prune_expired()?;
+
+        assert!(!cache.file_path("stale").exists()?);
+        assert!(cache.file_path("fresh").exists()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn prune_expired_and_get_fresh_ignore_non_ttl_entries() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let cache: SimpleFileCache = SimpleFileCache::temp()?;
+        cache.put("plain", "data")?;
+        cache.put_verified("verified", "data")?;
+
+        assert_eq!(cache.get_fresh("plain")?, None);
+        assert_eq!(cache.get_fresh("verified")?, None);
+
+        cache.prune_expired()?;
+
+        assert!(cache.file_path("plain").exists()?);
+        assert!(cache.file_path("verified").exists()?);
+        assert!(cache.get("plain")?.is_some());
+        assert!(cache.get_verified("verified")?.is_some());
+
+        Ok(())
+    }
 }